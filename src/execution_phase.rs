@@ -11,62 +11,430 @@ struct PolymarketMidpointResponse {
     mid: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct PolymarketPriceResponse {
+    price: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OrderLevel {
+    price: String,
+    size: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OrderBook {
+    bids: Vec<OrderLevel>,
+    asks: Vec<OrderLevel>,
+}
+
+/// A priced token observation. `spread` is `0.0` outside of `FetchMode::Orderbook`,
+/// since the `/midpoint` and `/price` endpoints don't expose a book to derive it from.
+struct PriceObservation {
+    price: f64,
+    spread: f64,
+}
+
+// ============================================================================
+// FETCH MODE
+// ============================================================================
+
+/// Which Polymarket endpoint to source a token's price from. Selected via a
+/// `price:buy`, `price:sell`, `midpoint`, or `orderbook[:<depth>]` directive
+/// in the DR inputs; defaults to `Midpoint` when no directive is present.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FetchMode {
+    Midpoint,
+    Price(Side),
+    /// Depth-weighted mid computed from the order book, walking levels up to
+    /// `depth_notional` worth of size on each side.
+    Orderbook { depth_notional: f64 },
+}
+
+/// Default notional (price × size, not a raw size/share count - see
+/// `depth_weighted_price`) to walk into when a depth is not specified via
+/// an `orderbook:<depth>` directive.
+const DEFAULT_DEPTH_NOTIONAL: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    fn as_query_value(self) -> &'static str {
+        match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        }
+    }
+}
+
+impl FetchMode {
+    /// Parses a single directive such as `"midpoint"`, `"price:buy"`,
+    /// `"price:sell"`, `"orderbook"`, or `"orderbook:250"`.
+    fn parse(directive: &str) -> Option<Self> {
+        if directive == "midpoint" {
+            return Some(FetchMode::Midpoint);
+        }
+
+        if directive == "orderbook" {
+            return Some(FetchMode::Orderbook {
+                depth_notional: DEFAULT_DEPTH_NOTIONAL,
+            });
+        }
+
+        if let Some(depth) = directive.strip_prefix("orderbook:") {
+            return depth
+                .parse::<f64>()
+                .ok()
+                .map(|depth_notional| FetchMode::Orderbook { depth_notional });
+        }
+
+        match directive.strip_prefix("price:") {
+            Some("buy") => Some(FetchMode::Price(Side::Buy)),
+            Some("sell") => Some(FetchMode::Price(Side::Sell)),
+            _ => None,
+        }
+    }
+}
+
+/// Pulls the fetch-mode directive out of the DR inputs. Inputs look like
+/// `<directives>|<token_ids>`, so every segment except the last (the token
+/// ID list) is a directive; this is the only directive execution_phase
+/// currently understands, and falls back to `Midpoint` if none matches.
+fn fetch_mode_from_inputs(raw: &str) -> FetchMode {
+    let segments: Vec<&str> = raw.trim().split('|').collect();
+    let directive_count = segments.len().saturating_sub(1);
+
+    segments[..directive_count]
+        .iter()
+        .find_map(|segment| FetchMode::parse(segment.trim()))
+        .unwrap_or(FetchMode::Midpoint)
+}
+
+/// Fetches a single token's price using the given mode. Errors are logged by
+/// the caller via `elog!` before being propagated.
+fn fetch_price(token_id: &str, mode: FetchMode) -> Result<PriceObservation> {
+    match mode {
+        FetchMode::Midpoint => {
+            log!("Fetching Polymarket midpoint data for token: {}", token_id);
+
+            let response = http_fetch(
+                format!("https://clob.polymarket.com/midpoint?token_id={}", token_id),
+                None,
+            );
+
+            if !response.is_ok() {
+                elog!(
+                    "Midpoint HTTP Response was rejected: {} - {}",
+                    response.status,
+                    String::from_utf8(response.bytes)?
+                );
+                anyhow::bail!("Error while fetching midpoint information");
+            }
+
+            let midpoint_data = serde_json::from_slice::<PolymarketMidpointResponse>(&response.bytes)?;
+
+            let price = midpoint_data.mid.parse::<f64>().map_err(|e| {
+                elog!("Failed to parse mid price '{}': {}", midpoint_data.mid, e);
+                anyhow::anyhow!("Failed to parse mid price")
+            })?;
+
+            Ok(PriceObservation { price, spread: 0.0 })
+        }
+        FetchMode::Price(side) => {
+            log!(
+                "Fetching Polymarket {} price data for token: {}",
+                side.as_query_value(),
+                token_id
+            );
+
+            let response = http_fetch(
+                format!(
+                    "https://clob.polymarket.com/price?token_id={}&side={}",
+                    token_id,
+                    side.as_query_value()
+                ),
+                None,
+            );
+
+            if !response.is_ok() {
+                elog!(
+                    "Price HTTP Response was rejected: {} - {}",
+                    response.status,
+                    String::from_utf8(response.bytes)?
+                );
+                anyhow::bail!("Error while fetching price information");
+            }
+
+            let price_data = serde_json::from_slice::<PolymarketPriceResponse>(&response.bytes)?;
+
+            let price = price_data.price.parse::<f64>().map_err(|e| {
+                elog!("Failed to parse price '{}': {}", price_data.price, e);
+                anyhow::anyhow!("Failed to parse price")
+            })?;
+
+            Ok(PriceObservation { price, spread: 0.0 })
+        }
+        FetchMode::Orderbook { depth_notional } => fetch_orderbook_price(token_id, depth_notional),
+    }
+}
+
+/// Fetches the order book for `token_id` and derives the best bid/ask,
+/// spread, and a depth-weighted mid (the volume-weighted average price on
+/// each side, walking levels until `depth_notional` worth of size is
+/// covered). Rejects the token if the book is empty or crossed, since
+/// neither case yields a trustworthy price.
+fn fetch_orderbook_price(token_id: &str, depth_notional: f64) -> Result<PriceObservation> {
+    log!(
+        "Fetching Polymarket order book data for token: {} (depth {})",
+        token_id,
+        depth_notional
+    );
+
+    let response = http_fetch(
+        format!("https://clob.polymarket.com/book?token_id={}", token_id),
+        None,
+    );
+
+    if !response.is_ok() {
+        elog!(
+            "Book HTTP Response was rejected: {} - {}",
+            response.status,
+            String::from_utf8(response.bytes)?
+        );
+        anyhow::bail!("Error while fetching order book information");
+    }
+
+    let mut book = serde_json::from_slice::<OrderBook>(&response.bytes)?;
+
+    if book.bids.is_empty() || book.asks.is_empty() {
+        anyhow::bail!("Order book for token {token_id} is empty");
+    }
+
+    // Reject a malformed level up front, the same way `depth_weighted_price`
+    // does, so it can't silently sort as the best (or worst) price below.
+    for level in book.bids.iter().chain(book.asks.iter()) {
+        parse_level_price(level)?;
+    }
+
+    // Best bid is the highest bid price, best ask is the lowest ask price.
+    book.bids.sort_by(|a, b| {
+        parse_level_price(b)
+            .unwrap()
+            .partial_cmp(&parse_level_price(a).unwrap())
+            .unwrap()
+    });
+    book.asks.sort_by(|a, b| {
+        parse_level_price(a)
+            .unwrap()
+            .partial_cmp(&parse_level_price(b).unwrap())
+            .unwrap()
+    });
+
+    let best_bid = parse_level_price(&book.bids[0])?;
+    let best_ask = parse_level_price(&book.asks[0])?;
+
+    if best_bid >= best_ask {
+        anyhow::bail!("Order book for token {token_id} is crossed: bid {best_bid} >= ask {best_ask}");
+    }
+
+    let weighted_bid = depth_weighted_price(&book.bids, depth_notional)?;
+    let weighted_ask = depth_weighted_price(&book.asks, depth_notional)?;
+
+    Ok(PriceObservation {
+        price: (weighted_bid + weighted_ask) / 2.0,
+        spread: best_ask - best_bid,
+    })
+}
+
+/// Parses a level's price, erroring - like `depth_weighted_price` does for
+/// the same data - rather than silently treating a malformed level as price
+/// `0.0`, which could otherwise sort in as a false best bid/ask.
+fn parse_level_price(level: &OrderLevel) -> Result<f64> {
+    level
+        .price
+        .parse::<f64>()
+        .map_err(|err| anyhow::anyhow!("invalid order book level price '{}': {err}", level.price))
+}
+
+/// Volume-weighted average price across `levels`, walking in book order
+/// until `depth_notional` worth of size has been covered (or the book side
+/// is exhausted, whichever comes first).
+fn depth_weighted_price(levels: &[OrderLevel], depth_notional: f64) -> Result<f64> {
+    let mut notional_covered = 0.0;
+    let mut size_covered = 0.0;
+    let mut weighted_sum = 0.0;
+
+    for level in levels {
+        let price = level.price.parse::<f64>()?;
+        let size = level.size.parse::<f64>()?;
+        let level_notional = price * size;
+
+        let remaining = depth_notional - notional_covered;
+        if remaining <= 0.0 {
+            break;
+        }
+
+        let take_notional = level_notional.min(remaining);
+        let take_size = take_notional / price;
+
+        weighted_sum += price * take_size;
+        size_covered += take_size;
+        notional_covered += take_notional;
+    }
+
+    if size_covered == 0.0 {
+        anyhow::bail!("no liquidity within depth {depth_notional}");
+    }
+
+    Ok(weighted_sum / size_covered)
+}
+
+// ============================================================================
+// REVEAL ENCODING
+// ============================================================================
+//
+// Reveals are handed from execution_phase to tally_phase as a compact fixed-
+// width binary format instead of JSON, so consensus never depends on f64
+// text round-tripping (different nodes' float formatters are not guaranteed
+// to agree bit-for-bit). Wire format:
+//
+//   header:  1 byte format version | 2 bytes entry count (big-endian)
+//   entry*:  1 byte scale | 15 byte hashed token ID | 8 byte scaled price (BE)
+//            | 8 byte timestamp ms (BE) | 8 byte scaled spread (BE)
+//
+// Each entry is exactly 40 bytes. `spread` is `0` outside of
+// `FetchMode::Orderbook`, which is the only mode that has one to report.
+
+const REVEAL_FORMAT_VERSION: u8 = 2;
+
+/// Number of decimal places the scaled price and spread have been multiplied
+/// by (i.e. `scale = 6` means the on-wire values are the real values * 1_000_000).
+const PRICE_SCALE: u8 = 6;
+
+/// One token's price observation, ready to be packed into a fixed-width row.
+struct RevealEntry {
+    scale: u8,
+    /// Polymarket token IDs are far wider than fits in a fixed-width row, so
+    /// we carry a hash of the ID rather than the ID itself; tally_phase
+    /// re-derives the same hash from the DR inputs to line entries back up.
+    token_id_hash: [u8; 15],
+    scaled_price: u64,
+    timestamp_ms: u64,
+    /// Best-ask minus best-bid, scaled like `scaled_price`. Only populated
+    /// for `FetchMode::Orderbook` observations; `0` otherwise.
+    scaled_spread: u64,
+}
+
+/// Hashes a token ID down to 15 bytes using FNV-1a (no cryptographic
+/// properties needed here - this just has to fit the fixed-width row and
+/// be deterministic across nodes). `pub(crate)` so tally_phase can
+/// re-derive the same hash from the DR inputs and verify reveals against it.
+pub(crate) fn hash_token_id(token_id: &str) -> [u8; 15] {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut h1 = FNV_OFFSET;
+    for byte in token_id.as_bytes() {
+        h1 ^= *byte as u64;
+        h1 = h1.wrapping_mul(FNV_PRIME);
+    }
+
+    // Re-hash the first digest with a different seed to fill the remaining bytes.
+    let mut h2 = h1;
+    for byte in token_id.as_bytes() {
+        h2 ^= *byte as u64;
+        h2 = h2.wrapping_mul(FNV_PRIME).wrapping_add(1);
+    }
+
+    let mut out = [0u8; 15];
+    out[..8].copy_from_slice(&h1.to_be_bytes());
+    out[8..].copy_from_slice(&h2.to_be_bytes()[..7]);
+    out
+}
+
+/// Packs reveal entries into the fixed-width binary wire format described above.
+fn encode_reveal(entries: &[RevealEntry]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(3 + entries.len() * 40);
+    out.push(REVEAL_FORMAT_VERSION);
+    out.extend_from_slice(&(entries.len() as u16).to_be_bytes());
+
+    for entry in entries {
+        out.push(entry.scale);
+        out.extend_from_slice(&entry.token_id_hash);
+        out.extend_from_slice(&entry.scaled_price.to_be_bytes());
+        out.extend_from_slice(&entry.timestamp_ms.to_be_bytes());
+        out.extend_from_slice(&entry.scaled_spread.to_be_bytes());
+    }
+
+    out
+}
+
 // ============================================================================
 // EXECUTION PHASE - FETCHES LIVE DATA FROM POLYMARKET
 // ============================================================================
 
 /**
  * Executes the data request phase within the SEDA network.
- * This phase fetches midpoint prices for Polymarket tokens based on comma-separated token ID inputs.
+ * This phase fetches prices for Polymarket tokens based on comma-separated token ID inputs,
+ * sourced from the `/midpoint`, `/price`, or `/book` endpoint depending on the fetch-mode directive.
  */
 pub fn execution_phase() -> Result<()> {
     // Retrieve the input parameters for the data request (DR).
-    // Expected to be comma-separated token IDs (e.g., "47060861968389645577251408086188258199430417779776802737050665875266354301946").
+    // Expected to be comma-separated token IDs (e.g., "47060861968389645577251408086188258199430417779776802737050665875266354301946"),
+    // optionally preceded by `|`-separated directives such as `price:buy` or `midpoint`.
 
     let dr_inputs_raw = String::from_utf8(Process::get_inputs())?;
 
     let dr_inputs_trimmed = dr_inputs_raw.trim();
 
-    let token_ids: Vec<&str> = dr_inputs_trimmed.split(',').collect();
-
-    let mut mids: Vec<f64> = Vec::new();
-
-    for token_id in token_ids {
-        log!("Fetching Polymarket midpoint data for token: {}", token_id);
+    // Inputs are `<directives>|<token_ids>`, where directives configure things
+    // like the fetch mode here and the tally phase's aggregation mode. Only
+    // the final segment holds the token IDs.
+    let ids_segment = dr_inputs_trimmed
+        .rsplit('|')
+        .next()
+        .unwrap_or(dr_inputs_trimmed);
 
-        // Step 1: Get midpoint information
-        let midpoint_response = http_fetch(
-            format!("https://clob.polymarket.com/midpoint?token_id={}", token_id),
-            None,
-        );
+    let token_ids: Vec<&str> = ids_segment.split(',').collect();
+    let fetch_mode = fetch_mode_from_inputs(dr_inputs_trimmed);
 
-        // Check if the midpoint request was successful
-        if !midpoint_response.is_ok() {
-            elog!(
-                "Midpoint HTTP Response was rejected: {} - {}",
-                midpoint_response.status,
-                String::from_utf8(midpoint_response.bytes)?
-            );
-            Process::error("Error while fetching midpoint information".as_bytes());
-            continue;
-        }
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as u64;
 
-        // Parse midpoint information
-        let midpoint_data =
-            serde_json::from_slice::<PolymarketMidpointResponse>(&midpoint_response.bytes)?;
+    let mut entries: Vec<RevealEntry> = Vec::new();
 
-        // Parse the mid price from string to f64
-        let mid_price = midpoint_data.mid.parse::<f64>().map_err(|e| {
-            elog!("Failed to parse mid price '{}': {}", midpoint_data.mid, e);
-            anyhow::anyhow!("Failed to parse mid price")
-        })?;
-
-        log!("Fetched MID Price: ${}", mid_price);
+    for token_id in token_ids {
+        match fetch_price(token_id, fetch_mode) {
+            Ok(observation) => {
+                log!(
+                    "Fetched price: ${} (spread ${})",
+                    observation.price,
+                    observation.spread
+                );
 
-        mids.push(mid_price);
+                let scale_factor = 10f64.powi(PRICE_SCALE as i32);
+                entries.push(RevealEntry {
+                    scale: PRICE_SCALE,
+                    token_id_hash: hash_token_id(token_id),
+                    scaled_price: (observation.price * scale_factor).round() as u64,
+                    timestamp_ms,
+                    scaled_spread: (observation.spread * scale_factor).round() as u64,
+                });
+            }
+            Err(err) => {
+                elog!("Failed to fetch price for token {}: {err}", token_id);
+                Process::error("Error while fetching price information".as_bytes());
+                continue;
+            }
+        }
     }
 
-    let mids_bytes = serde_json::to_vec(&mids)?;
-    Process::success(&mids_bytes);
+    let reveal_bytes = encode_reveal(&entries);
+    Process::success(&reveal_bytes);
     Ok(())
 }
@@ -1,6 +1,375 @@
 use anyhow::Result;
+use ethabi::ethereum_types::U256;
+use ethabi::Token;
 use seda_sdk_rs::{elog, get_reveals, log, Process};
 
+use crate::execution_phase::hash_token_id;
+
+// ============================================================================
+// AGGREGATION
+// ============================================================================
+
+/// How per-token prices from multiple oracle nodes are combined into a
+/// single consensus value. Selected via a mode directive in the DR inputs
+/// so different markets can trade off robustness against responsiveness.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AggregationMode {
+    Median,
+    Mean,
+    Min,
+    Max,
+    /// Discards the top/bottom `p` fraction (0.0..0.5) of values before averaging.
+    TrimmedMean(f64),
+}
+
+impl AggregationMode {
+    /// Parses a single directive such as `"median"`, `"mean"`, `"min"`, `"max"`,
+    /// or `"trimmed_mean:0.1"`.
+    fn parse(directive: &str) -> Option<Self> {
+        if let Some(p) = directive.strip_prefix("trimmed_mean:") {
+            return p
+                .parse::<f64>()
+                .ok()
+                .map(|p| AggregationMode::TrimmedMean(p.clamp(0.0, 0.49)));
+        }
+
+        match directive {
+            "median" => Some(AggregationMode::Median),
+            "mean" => Some(AggregationMode::Mean),
+            "min" => Some(AggregationMode::Min),
+            "max" => Some(AggregationMode::Max),
+            _ => None,
+        }
+    }
+}
+
+/// Default minimum number of valid reveals required to produce an
+/// aggregate, when no `quorum:<n>` directive is present. `1` would let a
+/// single surviving reveal - possibly the one outlier this feature exists
+/// to defend against - stand in as consensus, so we require at least two.
+const DEFAULT_MIN_QUORUM: usize = 2;
+
+/// Pulls the aggregation-mode directive out of the DR inputs. Inputs look
+/// like `<directives>|<token_ids>`, so every segment except the last (the
+/// token ID list) is a directive; the first segment that parses as an
+/// aggregation mode wins, and this falls back to `Median` if none matches.
+/// Other directives (`quorum:<n>`, `hex`/`raw`) are read by their own helpers.
+fn aggregation_mode_from_inputs(raw: &str) -> AggregationMode {
+    let segments: Vec<&str> = raw.trim().split('|').collect();
+    let directive_count = segments.len().saturating_sub(1);
+
+    segments[..directive_count]
+        .iter()
+        .find_map(|segment| AggregationMode::parse(segment.trim()))
+        .unwrap_or(AggregationMode::Median)
+}
+
+/// Pulls the `quorum:<n>` directive out of the DR inputs the same way
+/// `aggregation_mode_from_inputs` does, falling back to `DEFAULT_MIN_QUORUM`
+/// if none matches or the value fails to parse.
+fn min_quorum_from_inputs(raw: &str) -> usize {
+    let segments: Vec<&str> = raw.trim().split('|').collect();
+    let directive_count = segments.len().saturating_sub(1);
+
+    segments[..directive_count]
+        .iter()
+        .find_map(|segment| segment.trim().strip_prefix("quorum:"))
+        .and_then(|n| n.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MIN_QUORUM)
+}
+
+/// Combines the per-node values for a single token into one price. `values`
+/// is sorted in place since every mode above needs order.
+fn aggregate_one(values: &mut [f64], mode: AggregationMode) -> f64 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let n = values.len();
+
+    match mode {
+        AggregationMode::Min => values[0],
+        AggregationMode::Max => values[n - 1],
+        AggregationMode::Mean => values.iter().sum::<f64>() / n as f64,
+        AggregationMode::Median if n % 2 == 0 => (values[n / 2 - 1] + values[n / 2]) / 2.0,
+        AggregationMode::Median => values[n / 2],
+        AggregationMode::TrimmedMean(p) => {
+            let trim = ((n as f64) * p).floor() as usize;
+            let trimmed = &values[trim..n - trim];
+            trimmed.iter().sum::<f64>() / trimmed.len() as f64
+        }
+    }
+}
+
+/// A token's aggregated price and liquidity/spread signal, both already
+/// scaled (see `PRICE_SCALE` in execution_phase).
+#[derive(Debug, Clone, Copy)]
+struct AggregatedToken {
+    scaled_price: u64,
+    scaled_spread: u64,
+}
+
+/// Decodes every reveal with [`decode_reveal`], skipping reveals that fail
+/// to decode, whose entry count disagrees with `expected_token_id_hashes`
+/// (the DR's own token count, not whichever reveal happens to decode
+/// first - anchoring to a reveal would let a single Byzantine node of the
+/// wrong length redefine the canonical shape and DoS every honest reveal),
+/// or whose per-entry `token_id_hash` doesn't line up with
+/// `expected_token_id_hashes` (a handful of bad or reordering nodes
+/// shouldn't block consensus, and positional aggregation below depends on
+/// every surviving reveal agreeing on which row is which token), then
+/// aggregates each token's scaled price (using `mode`) and scaled spread
+/// (always by `Mean`, see the comment at its call site) across the
+/// surviving reveals. Errors if fewer than `min_quorum` reveals remain
+/// after filtering.
+fn aggregate_prices(
+    reveal_bodies: &[&[u8]],
+    expected_token_id_hashes: &[[u8; 15]],
+    mode: AggregationMode,
+    min_quorum: usize,
+) -> Result<Vec<AggregatedToken>> {
+    let mut parsed: Vec<Vec<RevealEntry>> = Vec::new();
+
+    for body in reveal_bodies {
+        let entries = match decode_reveal(body) {
+            Ok(entries) => entries,
+            Err(err) => {
+                elog!("Skipping malformed reveal: {err}");
+                continue;
+            }
+        };
+
+        if entries.len() != expected_token_id_hashes.len() {
+            elog!(
+                "Skipping reveal with mismatched length: expected {}, got {}",
+                expected_token_id_hashes.len(),
+                entries.len()
+            );
+            continue;
+        }
+
+        let mismatch = expected_token_id_hashes
+            .iter()
+            .zip(entries.iter())
+            .position(|(expected, entry)| *expected != entry.token_id_hash);
+        if let Some(index) = mismatch {
+            elog!("Skipping reveal with token ID hash mismatch at index {index}");
+            continue;
+        }
+
+        parsed.push(entries);
+    }
+
+    if parsed.len() < min_quorum {
+        anyhow::bail!(
+            "Only {} valid reveal(s), need at least {min_quorum}",
+            parsed.len()
+        );
+    }
+
+    let token_count = expected_token_id_hashes.len();
+    let mut aggregated = Vec::with_capacity(token_count);
+
+    for i in 0..token_count {
+        let mut prices: Vec<f64> = parsed.iter().map(|p| p[i].scaled_price as f64).collect();
+        let mut spreads: Vec<f64> = parsed.iter().map(|p| p[i].scaled_spread as f64).collect();
+
+        aggregated.push(AggregatedToken {
+            scaled_price: aggregate_one(&mut prices, mode).round() as u64,
+            // Always `Mean`, regardless of `mode`: under `Min`/`Max` the
+            // price-selecting node isn't necessarily the spread-selecting
+            // node, so aggregating spread under the same mode could report
+            // a price/spread pair no single node ever observed together.
+            // `Mean` doesn't pick any single node's value, so it stays
+            // independent of which node's price was selected.
+            scaled_spread: aggregate_one(&mut spreads, AggregationMode::Mean).round() as u64,
+        });
+    }
+
+    Ok(aggregated)
+}
+
+// ============================================================================
+// REVEAL DECODING
+// ============================================================================
+//
+// Mirrors the fixed-width binary format execution_phase encodes reveals
+// into. Wire format:
+//
+//   header:  1 byte format version | 2 bytes entry count (big-endian)
+//   entry*:  1 byte scale | 15 byte hashed token ID | 8 byte scaled price (BE)
+//            | 8 byte timestamp ms (BE) | 8 byte scaled spread (BE)
+//
+// Each entry is exactly 40 bytes. `spread` is `0` for non-orderbook
+// observations.
+
+const REVEAL_FORMAT_VERSION: u8 = 2;
+const REVEAL_ENTRY_LEN: usize = 40;
+const REVEAL_HEADER_LEN: usize = 3;
+
+/// One token's price observation, unpacked from a fixed-width row.
+struct RevealEntry {
+    #[allow(dead_code)]
+    scale: u8,
+    /// Verified against `expected_token_id_hashes` in [`aggregate_prices`]
+    /// before this entry is trusted to sit at its row's index.
+    token_id_hash: [u8; 15],
+    scaled_price: u64,
+    #[allow(dead_code)]
+    timestamp_ms: u64,
+    /// Best-ask minus best-bid, scaled like `scaled_price`; `0` unless the
+    /// reveal came from `FetchMode::Orderbook`.
+    scaled_spread: u64,
+}
+
+/// Unpacks a reveal from the fixed-width binary wire format described above,
+/// validating the format version and that the body length matches the
+/// entry count declared in the header.
+fn decode_reveal(bytes: &[u8]) -> Result<Vec<RevealEntry>> {
+    if bytes.len() < REVEAL_HEADER_LEN {
+        anyhow::bail!("reveal too short: {} byte(s)", bytes.len());
+    }
+
+    let version = bytes[0];
+    if version != REVEAL_FORMAT_VERSION {
+        anyhow::bail!("unsupported reveal format version {version}");
+    }
+
+    let count = u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
+    let expected_len = REVEAL_HEADER_LEN + count * REVEAL_ENTRY_LEN;
+    if bytes.len() != expected_len {
+        anyhow::bail!(
+            "reveal length mismatch: expected {expected_len} byte(s) for {count} entries, got {}",
+            bytes.len()
+        );
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    for i in 0..count {
+        let start = REVEAL_HEADER_LEN + i * REVEAL_ENTRY_LEN;
+        let row = &bytes[start..start + REVEAL_ENTRY_LEN];
+
+        let mut token_id_hash = [0u8; 15];
+        token_id_hash.copy_from_slice(&row[1..16]);
+
+        entries.push(RevealEntry {
+            scale: row[0],
+            token_id_hash,
+            scaled_price: u64::from_be_bytes(row[16..24].try_into().unwrap()),
+            timestamp_ms: u64::from_be_bytes(row[24..32].try_into().unwrap()),
+            scaled_spread: u64::from_be_bytes(row[32..40].try_into().unwrap()),
+        });
+    }
+
+    Ok(entries)
+}
+
+// ============================================================================
+// ABI ENCODING
+// ============================================================================
+
+/// Pulls the token ID list out of the DR inputs. Inputs look like
+/// `<directives>|<token_ids>`, so the token IDs are always the final
+/// comma-separated segment.
+fn token_ids_from_inputs(raw: &str) -> Result<Vec<U256>> {
+    let ids_segment = raw.trim().rsplit('|').next().unwrap_or("");
+
+    ids_segment
+        .split(',')
+        .map(|id| {
+            U256::from_dec_str(id.trim())
+                .map_err(|err| anyhow::anyhow!("Invalid token ID '{id}': {err}"))
+        })
+        .collect()
+}
+
+/// Re-derives the hash execution_phase packed into each reveal row, in DR
+/// input order, so `aggregate_prices` can verify every reveal lines its
+/// entries up with the same tokens before aggregating positionally. Hashes
+/// the same raw (untrimmed) segments execution_phase hashes, so the two
+/// sides agree bit-for-bit.
+fn expected_token_id_hashes_from_inputs(raw: &str) -> Vec<[u8; 15]> {
+    let ids_segment = raw.trim().rsplit('|').next().unwrap_or("");
+    ids_segment.split(',').map(hash_token_id).collect()
+}
+
+/// ABI-encodes the tally result as `(uint256[] tokenIds, uint256[] prices,
+/// uint256[] spreads)` so the consuming contract can match each price back
+/// to its market instead of relying on positional ordering, and gate on
+/// spread as a liquidity signal.
+fn abi_encode_result(token_ids: &[U256], tokens: &[AggregatedToken]) -> Vec<u8> {
+    let token_id_tokens = token_ids.iter().copied().map(Token::Uint).collect();
+    let price_tokens = tokens
+        .iter()
+        .map(|t| Token::Uint(U256::from(t.scaled_price)))
+        .collect();
+    let spread_tokens = tokens
+        .iter()
+        .map(|t| Token::Uint(U256::from(t.scaled_spread)))
+        .collect();
+
+    ethabi::encode(&[
+        Token::Array(token_id_tokens),
+        Token::Array(price_tokens),
+        Token::Array(spread_tokens),
+    ])
+}
+
+// ============================================================================
+// OUTPUT FORMAT
+// ============================================================================
+
+/// How the final ABI-encoded result is surfaced to `Process::success`.
+/// Selected via a `hex` directive in the DR inputs; defaults to `Raw` (the
+/// ABI bytes as-is) so on-chain consumers see no behavior change.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Raw,
+    /// `0x`-prefixed lowercase hex text, for relayers that log or forward
+    /// the result as a string rather than raw bytes.
+    Hex,
+}
+
+impl OutputFormat {
+    fn parse(directive: &str) -> Option<Self> {
+        match directive {
+            "hex" => Some(OutputFormat::Hex),
+            "raw" => Some(OutputFormat::Raw),
+            _ => None,
+        }
+    }
+}
+
+/// Pulls the output-format directive out of the DR inputs the same way
+/// `aggregation_mode_from_inputs` does.
+fn output_format_from_inputs(raw: &str) -> OutputFormat {
+    let segments: Vec<&str> = raw.trim().split('|').collect();
+    let directive_count = segments.len().saturating_sub(1);
+
+    segments[..directive_count]
+        .iter()
+        .find_map(|segment| OutputFormat::parse(segment.trim()))
+        .unwrap_or(OutputFormat::Raw)
+}
+
+/// Prepends `0x` to the lowercase hex encoding of `bytes` - the canonical
+/// text form relayers can log or forward without re-deriving it.
+fn serialize_hex(bytes: &[u8]) -> String {
+    let mut out = vec![0u8; 2 + bytes.len() * 2];
+    out[0] = b'0';
+    out[1] = b'x';
+    hex::encode_to_slice(bytes, &mut out[2..]).expect("out is sized exactly for bytes' hex encoding");
+    String::from_utf8(out).expect("hex encoding is always valid UTF-8")
+}
+
+/// Parses a `0x`-prefixed lowercase hex string back into bytes, erroring
+/// clearly if the prefix is missing.
+#[allow(dead_code)]
+fn deserialize_hex(encoded: &str) -> Result<Vec<u8>> {
+    let stripped = encoded
+        .strip_prefix("0x")
+        .ok_or_else(|| anyhow::anyhow!("hex string '{encoded}' is missing the 0x prefix"))?;
+
+    Ok(hex::decode(stripped)?)
+}
+
 pub fn tally_phase() -> Result<()> {
     // Retrieve consensus reveals from the tally phase.
     let reveals = get_reveals()?;
@@ -11,51 +380,241 @@ pub fn tally_phase() -> Result<()> {
         return Ok(());
     }
 
-    // Take the first reveal as the result since we're expecting just the mid price from the API
-    let first_reveal = &reveals[0];
-    let prices = match serde_json::from_slice::<Vec<f64>>(&first_reveal.body.reveal) {
-        Ok(prices) => prices,
+    let dr_inputs_raw = String::from_utf8(Process::get_inputs())?;
+    let mode = aggregation_mode_from_inputs(&dr_inputs_raw);
+    let min_quorum = min_quorum_from_inputs(&dr_inputs_raw);
+    log!(
+        "Aggregating {} reveal(s) using {mode:?}, requiring quorum {min_quorum}",
+        reveals.len()
+    );
+
+    let expected_token_id_hashes = expected_token_id_hashes_from_inputs(&dr_inputs_raw);
+    let reveal_bodies: Vec<&[u8]> = reveals.iter().map(|r| r.body.reveal.as_slice()).collect();
+    let aggregated = match aggregate_prices(&reveal_bodies, &expected_token_id_hashes, mode, min_quorum) {
+        Ok(aggregated) => aggregated,
         Err(err) => {
-            elog!("Failed to parse revealed prices: {err}");
-            Process::error("Failed to parse revealed prices".as_bytes());
+            elog!("Failed to aggregate revealed prices: {err}");
+            Process::error("Failed to aggregate revealed prices".as_bytes());
             return Ok(());
         }
     };
 
-    log!("Final prices: {prices:?}");
+    log!("Aggregated tokens for EVM: {aggregated:?}");
 
-    // Convert f64 prices to scaled integers (multiply by 1,000,000 to preserve 6 decimal places)
-    // For example: 0.105 -> 105000, 0.895 -> 895000
-    let scaled_prices: Vec<u64> = prices
-        .iter()
-        .map(|&price| (price * 1_000_000.0) as u64)
-        .collect();
+    let token_ids = match token_ids_from_inputs(&dr_inputs_raw) {
+        Ok(token_ids) => token_ids,
+        Err(err) => {
+            elog!("Failed to parse token IDs from DR inputs: {err}");
+            Process::error("Failed to parse token IDs from DR inputs".as_bytes());
+            return Ok(());
+        }
+    };
+
+    if token_ids.len() != aggregated.len() {
+        elog!(
+            "Token ID count ({}) does not match price count ({})",
+            token_ids.len(),
+            aggregated.len()
+        );
+        Process::error("Token ID count does not match price count".as_bytes());
+        return Ok(());
+    }
+
+    let abi_encoded = abi_encode_result(&token_ids, &aggregated);
+
+    log!("ABI-encoded data length: {} bytes", abi_encoded.len());
+
+    let format = output_format_from_inputs(&dr_inputs_raw);
+    match format {
+        OutputFormat::Raw => Process::success(&abi_encoded),
+        OutputFormat::Hex => Process::success(serialize_hex(&abi_encoded).as_bytes()),
+    }
 
-    log!("Scaled prices for EVM: {scaled_prices:?}");
+    Ok(())
+}
 
-    // Create ABI-encoded data for uint256[] that Solidity can decode
-    let mut abi_encoded = Vec::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // ABI encoding for dynamic array uint256[]:
-    // 1. Offset to array data (32 bytes) = 0x20
-    abi_encoded.extend_from_slice(&[0u8; 31]);
-    abi_encoded.push(0x20);
+    #[test]
+    fn abi_encode_result_matches_known_vector() {
+        let token_ids = vec![U256::from(1u64), U256::from(2u64)];
+        let tokens = vec![
+            AggregatedToken {
+                scaled_price: 105_000,
+                scaled_spread: 1_000,
+            },
+            AggregatedToken {
+                scaled_price: 895_000,
+                scaled_spread: 2_000,
+            },
+        ];
 
-    // 2. Array length (32 bytes)
-    let array_length = scaled_prices.len() as u64;
-    abi_encoded.extend_from_slice(&[0u8; 24]);
-    abi_encoded.extend_from_slice(&array_length.to_be_bytes());
+        let encoded = abi_encode_result(&token_ids, &tokens);
 
-    // 3. Array elements (each 32 bytes)
-    for price in scaled_prices {
-        abi_encoded.extend_from_slice(&[0u8; 24]);
-        abi_encoded.extend_from_slice(&price.to_be_bytes());
+        let expected = "\
+            0000000000000000000000000000000000000000000000000000000000000060\
+            00000000000000000000000000000000000000000000000000000000000000c0\
+            0000000000000000000000000000000000000000000000000000000000000120\
+            0000000000000000000000000000000000000000000000000000000000000002\
+            0000000000000000000000000000000000000000000000000000000000000001\
+            0000000000000000000000000000000000000000000000000000000000000002\
+            0000000000000000000000000000000000000000000000000000000000000002\
+            0000000000000000000000000000000000000000000000000000000000019a28\
+            00000000000000000000000000000000000000000000000000000000000da818\
+            0000000000000000000000000000000000000000000000000000000000000002\
+            00000000000000000000000000000000000000000000000000000000000003e8\
+            00000000000000000000000000000000000000000000000000000000000007d0";
+
+        assert_eq!(hex::encode(&encoded), expected);
     }
 
-    log!("ABI-encoded data length: {} bytes", abi_encoded.len());
+    #[test]
+    fn expected_token_id_hashes_from_inputs_matches_hash_token_id() {
+        let hashes = expected_token_id_hashes_from_inputs("median|111,222");
+        assert_eq!(hashes, vec![hash_token_id("111"), hash_token_id("222")]);
+    }
 
-    // Report the successful result in the tally phase.
-    Process::success(&abi_encoded);
+    fn encode_reveal_for_test(entries: &[RevealEntry]) -> Vec<u8> {
+        let mut out = vec![REVEAL_FORMAT_VERSION];
+        out.extend_from_slice(&(entries.len() as u16).to_be_bytes());
+        for entry in entries {
+            out.push(entry.scale);
+            out.extend_from_slice(&entry.token_id_hash);
+            out.extend_from_slice(&entry.scaled_price.to_be_bytes());
+            out.extend_from_slice(&entry.timestamp_ms.to_be_bytes());
+            out.extend_from_slice(&entry.scaled_spread.to_be_bytes());
+        }
+        out
+    }
 
-    Ok(())
+    #[test]
+    fn aggregate_prices_skips_reveal_with_mismatched_token_id_hash() {
+        let expected_hashes = vec![hash_token_id("111")];
+
+        let good_reveal = encode_reveal_for_test(&[RevealEntry {
+            scale: 6,
+            token_id_hash: hash_token_id("111"),
+            scaled_price: 100,
+            timestamp_ms: 0,
+            scaled_spread: 0,
+        }]);
+        let mismatched_reveal = encode_reveal_for_test(&[RevealEntry {
+            scale: 6,
+            token_id_hash: hash_token_id("999"),
+            scaled_price: 999_999,
+            timestamp_ms: 0,
+            scaled_spread: 0,
+        }]);
+
+        let aggregated = aggregate_prices(
+            &[&good_reveal, &mismatched_reveal],
+            &expected_hashes,
+            AggregationMode::Median,
+            1,
+        )
+        .unwrap();
+
+        assert_eq!(aggregated.len(), 1);
+        assert_eq!(aggregated[0].scaled_price, 100);
+    }
+
+    #[test]
+    fn aggregate_prices_always_means_spread_even_under_min_max() {
+        let expected_hashes = vec![hash_token_id("111")];
+        let reveals: Vec<Vec<u8>> = [10u64, 20, 30]
+            .iter()
+            .map(|&scaled_spread| {
+                encode_reveal_for_test(&[RevealEntry {
+                    scale: 6,
+                    token_id_hash: hash_token_id("111"),
+                    scaled_price: 100,
+                    timestamp_ms: 0,
+                    scaled_spread,
+                }])
+            })
+            .collect();
+        let reveal_refs: Vec<&[u8]> = reveals.iter().map(|r| r.as_slice()).collect();
+
+        let aggregated =
+            aggregate_prices(&reveal_refs, &expected_hashes, AggregationMode::Max, 1).unwrap();
+
+        // Price picks the max (100, tied across all three); spread is the
+        // mean of 10/20/30 regardless of `mode`, not whichever node's
+        // spread happened to ride along with the selected price.
+        assert_eq!(aggregated[0].scaled_price, 100);
+        assert_eq!(aggregated[0].scaled_spread, 20);
+    }
+
+    #[test]
+    fn aggregate_prices_anchors_length_to_dr_inputs_not_first_reveal() {
+        // A Byzantine reveal with the wrong entry count decodes first, but
+        // must not redefine the canonical shape and drop the honest reveals.
+        let expected_hashes = vec![hash_token_id("111"), hash_token_id("222")];
+
+        let byzantine_short_reveal = encode_reveal_for_test(&[RevealEntry {
+            scale: 6,
+            token_id_hash: hash_token_id("111"),
+            scaled_price: 1,
+            timestamp_ms: 0,
+            scaled_spread: 0,
+        }]);
+        let honest_entry = |token_id: &str, scaled_price: u64| RevealEntry {
+            scale: 6,
+            token_id_hash: hash_token_id(token_id),
+            scaled_price,
+            timestamp_ms: 0,
+            scaled_spread: 0,
+        };
+        let honest_reveal_a =
+            encode_reveal_for_test(&[honest_entry("111", 100), honest_entry("222", 200)]);
+        let honest_reveal_b =
+            encode_reveal_for_test(&[honest_entry("111", 100), honest_entry("222", 200)]);
+
+        let aggregated = aggregate_prices(
+            &[&byzantine_short_reveal, &honest_reveal_a, &honest_reveal_b],
+            &expected_hashes,
+            AggregationMode::Median,
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(aggregated.len(), 2);
+        assert_eq!(aggregated[0].scaled_price, 100);
+        assert_eq!(aggregated[1].scaled_price, 200);
+    }
+
+    #[test]
+    fn min_quorum_from_inputs_defaults_and_parses() {
+        assert_eq!(min_quorum_from_inputs("1,2,3"), DEFAULT_MIN_QUORUM);
+        assert_eq!(min_quorum_from_inputs("quorum:3|1,2,3"), 3);
+        assert_eq!(min_quorum_from_inputs("median|quorum:5|1,2,3"), 5);
+        assert_eq!(min_quorum_from_inputs("quorum:bogus|1,2,3"), DEFAULT_MIN_QUORUM);
+    }
+
+    #[test]
+    fn output_format_from_inputs_defaults_to_raw() {
+        assert_eq!(output_format_from_inputs("1,2,3"), OutputFormat::Raw);
+        assert_eq!(output_format_from_inputs("median|1,2,3"), OutputFormat::Raw);
+        assert_eq!(output_format_from_inputs("hex|1,2,3"), OutputFormat::Hex);
+        assert_eq!(
+            output_format_from_inputs("median|hex|1,2,3"),
+            OutputFormat::Hex
+        );
+    }
+
+    #[test]
+    fn serialize_hex_round_trips_through_deserialize_hex() {
+        let bytes = vec![0xde, 0xad, 0xbe, 0xef];
+        let encoded = serialize_hex(&bytes);
+
+        assert_eq!(encoded, "0xdeadbeef");
+        assert_eq!(deserialize_hex(&encoded).unwrap(), bytes);
+    }
+
+    #[test]
+    fn deserialize_hex_requires_0x_prefix() {
+        assert!(deserialize_hex("deadbeef").is_err());
+    }
 }